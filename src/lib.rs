@@ -1,12 +1,15 @@
 use std::{
-    convert,
     fs,
-    io::{self, Read},
-    num,
+    io::Read,
+    mem::MaybeUninit,
     path,
 };
 
+use crate::math::cyclic_group::{Cyclic, CyclicIndex};
+
 pub mod application_root;
+pub mod math;
+pub mod sync;
 
 pub fn load_file_as_u8<P: AsRef<path::Path>>(file_path: &P) -> Box<[u8]> {
     let mut buf = Vec::new();
@@ -17,139 +20,184 @@ pub fn load_file_as_u8<P: AsRef<path::Path>>(file_path: &P) -> Box<[u8]> {
     buf.into_boxed_slice()
 }
 
-pub trait Cyclic<T: Sized>
-{
-    fn index(&self) -> T;
-    /// incrementing itself and then return the result
-    fn increment(&mut self) -> T;
-    /// decrementing itself and then return the result
-    fn decrement(&mut self) -> T;
-    /// incrementing itself and then return the result
-    fn increment_by(&mut self, num: usize) -> T;
-    /// decrementing itself and then return the result
-    fn decrement_by(&mut self, num: usize) -> T;
-}
-
-#[derive(Clone, Debug, Default)]
-pub struct CyclicIndex {
-    pub index: usize,
-    /// keep a clone of size to ensure locality at expense of double the memory use
-    pub size: usize,
-}
-
-impl CyclicIndex {
-    pub fn new(index: usize, size: usize) -> Self {
-        CyclicIndex {
-            index,
-            size,
-        }
-    }
-}
-
-impl convert::From<CyclicIndex> for usize {
-    fn from(cyclic_index: CyclicIndex) -> usize {
-        cyclic_index.index.clone()
-    }
-}
-
-impl Cyclic<usize> for CyclicIndex {
-    fn index(&self) -> usize {
-        self.index
-    }
-    fn increment(&mut self) -> usize {
-        // It seems that this will hardly overflow but should be allow to overflow just in case
-        self.index = (num::Wrapping(self.index) + num::Wrapping(1)).0 % self.size;
-        self.index
-    }
-    fn decrement(&mut self) -> usize {
-        // This will overflow and should be allow to overflow
-        self.index = (num::Wrapping(self.index) - num::Wrapping(1)).0 % self.size;
-        self.index
-    }
-    /// incrementing itself and then return the result
-    fn increment_by(&mut self, num: usize) -> usize {
-        self.index = (num::Wrapping(self.index) + num::Wrapping(num)).0 % self.size;
-        self.index
-    }
-    /// decrementing itself and then return the result
-    fn decrement_by(&mut self, num: usize) -> usize {
-        self.index = (num::Wrapping(self.index) + num::Wrapping(num)).0 % self.size;
-        self.index
-    }
-}
-
-#[derive(Clone, Debug)]
+/// A fixed-capacity ring buffer that remembers the last `capacity` values pushed into it.
+///
+/// Storage is backed by `Box<[MaybeUninit<T>]>` rather than pre-filled with placeholder values,
+/// so `T` does not need a `Default`-like bound and a `push` of the first `capacity` values does
+/// not need to allocate or initialize anything beyond what is actually stored.
+#[derive(Debug)]
 pub struct History<T> {
-    values: Box<[T]>,
+    values: Box<[MaybeUninit<T>]>,
     ptr: CyclicIndex,
     pub count: usize,
 }
 
-pub trait HistoryDefault {
-    fn history_default() -> Self;
-}
-
-impl<T> History<T>
-    where T: HistoryDefault + Clone {
+impl<T> History<T> {
     pub fn new(capacity: usize) -> Self {
         History {
-            values: vec![T::history_default(); capacity].into_boxed_slice(),
+            values: (0..capacity)
+                .map(|_| MaybeUninit::uninit())
+                .collect::<Vec<_>>()
+                .into_boxed_slice(),
             count: 0,
             ptr: CyclicIndex::new(0, capacity),
         }
     }
-    
+
     pub fn clear(&mut self) {
+        for slot in self.values.iter_mut().take(self.count) {
+            unsafe { slot.assume_init_drop(); }
+        }
         self.count = 0;
         self.ptr.index = 0;
     }
-    
+
     pub fn push(&mut self, value: T) {
         let capacity = self.values.len();
+        let index = self.ptr.index;
         if self.count < capacity {
             self.count += 1;
+        } else {
+            // the slot about to be overwritten already holds a live value
+            unsafe { self.values[index].assume_init_drop(); }
         }
-        self.values[self.ptr.index] = value;
+        self.values[index] = MaybeUninit::new(value);
         self.ptr.increment();
     }
 }
 
-pub struct HistoryIntoIterator<'a, T>
-    where T: Clone + HistoryDefault {
+impl<T> Drop for History<T> {
+    fn drop(&mut self) {
+        for slot in self.values.iter_mut().take(self.count) {
+            unsafe { slot.assume_init_drop(); }
+        }
+    }
+}
+
+impl<T> History<T> {
+    /// Borrowing iterator over the stored values, newest first. Unlike `into_iter` this does
+    /// not require `T: Clone`.
+    pub fn iter(&self) -> HistoryIter<'_, T> {
+        HistoryIter {
+            history: self,
+            front: 0,
+            back: self.count,
+        }
+    }
+}
+
+/// Maps a logical position (`0` = newest, `count - 1` = oldest) to the backing slot index.
+fn history_slot_for<T>(history: &History<T>, logical_index: usize) -> usize {
+    let len = history.values.len();
+    (len + history.ptr.index - 1 - logical_index) % len
+}
+
+pub struct HistoryIntoIterator<'a, T> {
     history: &'a History<T>,
-    index: usize,
+    front: usize,
+    back: usize,
 }
 
 impl<'a, T> IntoIterator for &'a History<T>
-    where T: Clone + HistoryDefault
+    where T: Clone
 {
     type Item = T;
     type IntoIter = HistoryIntoIterator<'a, T>;
-    
+
     fn into_iter(self) -> Self::IntoIter {
         HistoryIntoIterator {
             history: self,
-            index: 0,
+            front: 0,
+            back: self.count,
         }
     }
 }
 
 impl<'a, T> Iterator for HistoryIntoIterator<'a, T>
-    where T: Clone + HistoryDefault
+    where T: Clone
 {
     type Item = T;
-    
+
     fn next(&mut self) -> Option<T> {
-        if self.index >= self.history.count {
+        if self.front >= self.back {
             None
         } else {
-            let len = self.history.values.len();
-            let item = self.history.values[(len + self.history.ptr.index - 1 - self.index) % len]
-                .clone();
-            self.index += 1;
-            Some(item)
+            let slot = history_slot_for(self.history, self.front);
+            self.front += 1;
+            Some(unsafe { self.history.values[slot].assume_init_ref().clone() })
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.front;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for HistoryIntoIterator<'a, T>
+    where T: Clone
+{
+    fn next_back(&mut self) -> Option<T> {
+        if self.front >= self.back {
+            None
+        } else {
+            self.back -= 1;
+            let slot = history_slot_for(self.history, self.back);
+            Some(unsafe { self.history.values[slot].assume_init_ref().clone() })
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for HistoryIntoIterator<'a, T>
+    where T: Clone
+{
+    fn len(&self) -> usize {
+        self.back - self.front
+    }
+}
+
+/// Borrowing, non-cloning counterpart of `HistoryIntoIterator`. See `History::iter`.
+pub struct HistoryIter<'a, T> {
+    history: &'a History<T>,
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T> Iterator for HistoryIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.front >= self.back {
+            None
+        } else {
+            let slot = history_slot_for(self.history, self.front);
+            self.front += 1;
+            Some(unsafe { self.history.values[slot].assume_init_ref() })
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.front;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for HistoryIter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.front >= self.back {
+            None
+        } else {
+            self.back -= 1;
+            let slot = history_slot_for(self.history, self.back);
+            Some(unsafe { self.history.values[slot].assume_init_ref() })
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for HistoryIter<'a, T> {
+    fn len(&self) -> usize {
+        self.back - self.front
+    }
 }
 
 pub struct Cycle<T: Copy> {
@@ -166,16 +214,95 @@ impl<T> Cycle<T>
             index: 0,
         }
     }
-    
+
     pub fn get(&self) -> T { self.items[self.index] }
-    
+
     pub fn next(&mut self) -> T {
         self.index = (self.index + 1) % self.items.len();
         self.items[self.index]
     }
-    
+
     pub fn prev(&mut self) -> T {
         self.index = (self.index + self.items.len() - 1) % self.items.len();
         self.items[self.index]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[derive(Clone)]
+    struct DropCounter(Rc<Cell<usize>>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn push_overwrites_oldest_and_drops_it() {
+        let count = Rc::new(Cell::new(0));
+        let mut history = History::new(2);
+        history.push(DropCounter(count.clone()));
+        history.push(DropCounter(count.clone()));
+        assert_eq!(count.get(), 0);
+        history.push(DropCounter(count.clone()));
+        assert_eq!(count.get(), 1);
+    }
+
+    #[test]
+    fn clear_drops_all_live_values() {
+        let count = Rc::new(Cell::new(0));
+        let mut history = History::new(3);
+        history.push(DropCounter(count.clone()));
+        history.push(DropCounter(count.clone()));
+        history.clear();
+        assert_eq!(count.get(), 2);
+        assert_eq!(history.count, 0);
+    }
+
+    #[test]
+    fn drop_drops_all_live_values() {
+        let count = Rc::new(Cell::new(0));
+        {
+            let mut history = History::new(3);
+            history.push(DropCounter(count.clone()));
+            history.push(DropCounter(count.clone()));
+        }
+        assert_eq!(count.get(), 2);
+    }
+
+    #[test]
+    fn iter_yields_values_newest_first() {
+        let mut history = History::new(3);
+        history.push(1);
+        history.push(2);
+        history.push(3);
+        assert_eq!(history.iter().copied().collect::<Vec<_>>(), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn iter_len_and_rev_match_reversed_forward_order() {
+        let mut history = History::new(3);
+        history.push(1);
+        history.push(2);
+        history.push(3);
+        assert_eq!(history.iter().len(), 3);
+        assert_eq!(history.iter().rev().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn into_iter_len_and_rev_match_iter() {
+        let mut history = History::new(3);
+        history.push(1);
+        history.push(2);
+        history.push(3);
+        assert_eq!((&history).into_iter().collect::<Vec<_>>(), vec![3, 2, 1]);
+        assert_eq!((&history).into_iter().len(), 3);
+        assert_eq!((&history).into_iter().rev().collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+}
@@ -2,7 +2,7 @@
 //! while define a meta trait Cyclic as well
 
 use std::convert;
-use std::num;
+use std::fmt::Debug;
 use std::ops::{Add, AddAssign, Sub, SubAssign};
 
 //TODO: Impl foreign trait for type bound by local trait is impossible currently,
@@ -22,53 +22,168 @@ pub trait Cyclic: Sized + Copy
     fn decrement(&mut self) -> Self;
 }
 
+mod private {
+    /// Seals `Index` so that the wraparound arithmetic it promises is guaranteed correct for
+    /// every implementor; only the integer widths below may back a `CyclicIndex`.
+    pub trait Sealed {}
+}
+
+/// An unsigned integer width that can back a `CyclicIndex`.
+///
+/// Sealed (see `private::Sealed`): this is implemented for `u8`, `u16`, `u32`, `u64`, `u128` and
+/// `usize` only, analogous to how `std` uniformly exposes wrapping arithmetic across all integer
+/// widths.
+pub trait Index: private::Sealed + Copy + Default + Debug + PartialEq + PartialOrd {
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn wrapping_add(self, rhs: Self) -> Self;
+    fn wrapping_sub(self, rhs: Self) -> Self;
+    fn rem(self, rhs: Self) -> Self;
+}
+
+macro_rules! impl_index {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl private::Sealed for $t {}
+            impl Index for $t {
+                fn zero() -> Self { 0 }
+                fn one() -> Self { 1 }
+                fn wrapping_add(self, rhs: Self) -> Self { <$t>::wrapping_add(self, rhs) }
+                fn wrapping_sub(self, rhs: Self) -> Self { <$t>::wrapping_sub(self, rhs) }
+                fn rem(self, rhs: Self) -> Self { self % rhs }
+            }
+        )*
+    };
+}
+
+impl_index!(u8, u16, u32, u64, u128, usize);
+
+/// Adds `num` to `index` modulo `size`, reducing `num` mod `size` *before* touching native-width
+/// arithmetic so the result is correct even when `index + num` would overflow `I`'s own range
+/// (e.g. a `u8` index near 255 with a `size` well under that) — wrapping at `I`'s native width
+/// first, as a naive `index.wrapping_add(num).rem(size)` does, corrupts the result in that case.
+fn wrapping_add_mod<I: Index>(index: I, size: I, num: I) -> I {
+    let num = num.rem(size);
+    let headroom = size.wrapping_sub(index);
+    if num < headroom {
+        index.wrapping_add(num)
+    } else {
+        num.wrapping_sub(headroom)
+    }
+}
+
+/// Subtracts `num` from `index` modulo `size`, with the same native-width-overflow avoidance as
+/// `wrapping_add_mod`.
+fn wrapping_sub_mod<I: Index>(index: I, size: I, num: I) -> I {
+    let num = num.rem(size);
+    if num <= index {
+        index.wrapping_sub(num)
+    } else {
+        size.wrapping_sub(num.wrapping_sub(index))
+    }
+}
+
+/// A cyclic (wrapping) index into a collection of `size` elements.
+///
+/// Generic over the backing integer width `I`, which defaults to `usize` so existing code that
+/// writes `CyclicIndex` without turbofishing a width keeps compiling unchanged.
 #[derive(Clone, Debug, Default, Copy)]
-pub struct CyclicIndex {
-    pub index: usize,
+pub struct CyclicIndex<I: Index = usize> {
+    pub index: I,
     /// keep a clone of size to ensure locality at expense of double the memory use
-    pub size: usize,
+    pub size: I,
 }
 
-impl CyclicIndex {
-    pub fn new(index: usize, size: usize) -> Self {
+impl<I: Index> CyclicIndex<I> {
+    pub fn new(index: I, size: I) -> Self {
         CyclicIndex {
             index,
             size,
         }
     }
+
+    /// Fallible counterpart to `new`: rejects a `size` of `0` (which would make `% self.size`
+    /// panic on the first increment) and an `index` that is already out of range, instead of
+    /// silently accepting them.
+    pub fn try_new(index: I, size: I) -> Result<Self, CyclicIndexError<I>> {
+        if size == I::zero() {
+            return Err(CyclicIndexError::ZeroSize);
+        }
+        if index >= size {
+            return Err(CyclicIndexError::IndexOutOfBounds { index, size });
+        }
+        Ok(CyclicIndex { index, size })
+    }
+
+    /// Like `increment_by`, but reports whether the increment wrapped the index around `size`,
+    /// so callers building paging/cursor logic can detect cycle completion instead of silently
+    /// wrapping.
+    pub fn checked_increment_by(&mut self, num: I) -> bool {
+        // `size - self.index` is the raw distance to the boundary; comparing the un-reduced
+        // `num` against it (rather than computing `index + num` directly) reports the same
+        // wrap/no-wrap answer without risking overflow in `I`'s native width.
+        let wrapped = num >= self.size.wrapping_sub(self.index);
+        self.index = wrapping_add_mod(self.index, self.size, num);
+        wrapped
+    }
+
+    /// Like `decrement_by`, but reports whether the decrement wrapped the index around `size`.
+    pub fn checked_decrement_by(&mut self, num: I) -> bool {
+        // Compare the un-reduced `num` against `self.index` (mirroring `checked_increment_by`'s
+        // un-reduced comparison against the distance to the boundary), so that decrementing by
+        // an exact multiple of `size` is reported as a wrap, the same as incrementing by it.
+        let wrapped = num > self.index;
+        self.index = wrapping_sub_mod(self.index, self.size, num);
+        wrapped
+    }
+}
+
+/// Errors returned by `CyclicIndex::try_new` and its `TryFrom` conversion.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CyclicIndexError<I: Index> {
+    /// `size` was `0`, which would make every modulo operation panic.
+    ZeroSize,
+    /// `index` was `>= size`.
+    IndexOutOfBounds { index: I, size: I },
 }
 
-impl convert::From<CyclicIndex> for usize {
-    fn from(cyclic_index: CyclicIndex) -> usize {
-        cyclic_index.index.clone()
+impl convert::From<CyclicIndex<usize>> for usize {
+    fn from(cyclic_index: CyclicIndex<usize>) -> usize {
+        cyclic_index.index
     }
 }
 
-impl Cyclic for CyclicIndex {
-    type Value = usize;
+impl<I: Index> convert::TryFrom<(I, I)> for CyclicIndex<I> {
+    type Error = CyclicIndexError<I>;
+
+    fn try_from((index, size): (I, I)) -> Result<Self, Self::Error> {
+        CyclicIndex::try_new(index, size)
+    }
+}
 
-    fn value(&self) -> usize {
+impl<I: Index> Cyclic for CyclicIndex<I> {
+    type Value = I;
+
+    fn value(&self) -> I {
         self.index
     }
     fn increment(&mut self) -> Self {
-        self.increment_by(1)
+        self.increment_by(I::one())
     }
     fn decrement(&mut self) -> Self {
-        self.decrement_by(1)
+        self.decrement_by(I::one())
     }
     /// incrementing itself and then return the result
     #[inline]
-    fn increment_by(&mut self, num: usize) -> Self {
-        // It seems that this will hardly overflow but should be allow to overflow
-        self.index = (num::Wrapping(self.index) + num::Wrapping(num)).0 % self.size;
-        self.clone()
+    fn increment_by(&mut self, num: I) -> Self {
+        self.index = wrapping_add_mod(self.index, self.size, num);
+        *self
     }
     #[inline]
     /// decrementing itself and then return the result
-    fn decrement_by(&mut self, num: usize) -> Self {
-        // This will overflow and should be allow to overflow
-        self.index = (num::Wrapping(self.index) - num::Wrapping(num)).0 % self.size;
-        self.clone()
+    fn decrement_by(&mut self, num: I) -> Self {
+        self.index = wrapping_sub_mod(self.index, self.size, num);
+        *self
     }
 }
 
@@ -78,38 +193,40 @@ impl Cyclic for CyclicIndex {
 /// and i32 is not Into<usize>.
 /// one has to explicitly says a + 1usize in order for this to work,
 /// which defeats it purposes being T: Into<usize>
-impl Add<usize> for CyclicIndex {
-    type Output = CyclicIndex;
+impl<I: Index> Add<I> for CyclicIndex<I> {
+    type Output = CyclicIndex<I>;
 
-    fn add(self, rhs: usize) -> Self::Output {
-        self.clone().increment_by(rhs.into())
+    fn add(self, rhs: I) -> Self::Output {
+        let mut result = self;
+        result.increment_by(rhs)
     }
 }
 
-impl Sub<usize> for CyclicIndex
+impl<I: Index> Sub<I> for CyclicIndex<I>
 {
-    type Output = CyclicIndex;
+    type Output = CyclicIndex<I>;
 
-    fn sub(self, rhs: usize) -> Self::Output {
-        self.clone().decrement_by(rhs.into())
+    fn sub(self, rhs: I) -> Self::Output {
+        let mut result = self;
+        result.decrement_by(rhs)
     }
 }
 
-impl AddAssign<usize> for CyclicIndex
+impl<I: Index> AddAssign<I> for CyclicIndex<I>
 {
-    fn add_assign(&mut self, rhs: usize) {
-        self.increment_by(rhs.into());
+    fn add_assign(&mut self, rhs: I) {
+        self.increment_by(rhs);
     }
 }
 
-impl SubAssign<usize> for CyclicIndex
+impl<I: Index> SubAssign<I> for CyclicIndex<I>
 {
-    fn sub_assign(&mut self, rhs: usize) {
-        self.decrement_by(rhs.into());
+    fn sub_assign(&mut self, rhs: I) {
+        self.decrement_by(rhs);
     }
 }
 
-impl PartialEq for CyclicIndex {
+impl<I: Index> PartialEq for CyclicIndex<I> {
     fn eq(&self, other: &Self) -> bool {
         self.value() == other.value()
     }
@@ -119,89 +236,186 @@ impl PartialEq for CyclicIndex {
 mod test {
     use super::*;
 
-    macro_rules! c8 {
-        ($x:expr) => {
-            CyclicIndex::new($x,8)
-        };
-    }
-    #[test]
-    fn cyclic_index_increment_test() {
-        let mut a = c8!(0);
-        a.increment();
-        assert_eq!(a.value(), 1);
-        let mut b = c8!(7);
-        b.increment();
-        assert_eq!(b.value(), 0);
-    }
+    // The whole suite is parameterized over the backing integer width to prove the wraparound
+    // semantics hold for each width, not just for `usize`. It wraps at a `size` of 8; the `u8`
+    // tests after it cover a `size` close to the native width's own limit.
+    macro_rules! cyclic_index_tests {
+        ($mod_name:ident, $ty:ty) => {
+            mod $mod_name {
+                use super::*;
 
-    #[test]
-    fn cyclic_index_decrement_test() {
-        let mut a = c8!(0);
-        a.decrement();
-        assert_eq!(a.value(), 7);
-        let mut b = c8!(7);
-        b.decrement();
-        assert_eq!(b.value(), 6);
-    }
+                macro_rules! c8 {
+                    ($x:expr) => {
+                        CyclicIndex::<$ty>::new($x, 8)
+                    };
+                }
 
-    #[test]
-    fn cyclic_index_increment_by_test() {
-        let mut a = c8!(0);
-        a.increment_by(7);
-        assert_eq!(a.value(), 7);
-        let mut b = c8!(0);
-        b.increment_by(8);
-        assert_eq!(b.value(), 0);
-        let mut c = c8!(0);
-        c.increment_by(16);
-        assert_eq!(c.value(), 0);
+                #[test]
+                fn cyclic_index_increment_test() {
+                    let mut a = c8!(0);
+                    a.increment();
+                    assert_eq!(a.value(), 1);
+                    let mut b = c8!(7);
+                    b.increment();
+                    assert_eq!(b.value(), 0);
+                }
+
+                #[test]
+                fn cyclic_index_decrement_test() {
+                    let mut a = c8!(0);
+                    a.decrement();
+                    assert_eq!(a.value(), 7);
+                    let mut b = c8!(7);
+                    b.decrement();
+                    assert_eq!(b.value(), 6);
+                }
+
+                #[test]
+                fn cyclic_index_increment_by_test() {
+                    let mut a = c8!(0);
+                    a.increment_by(7);
+                    assert_eq!(a.value(), 7);
+                    let mut b = c8!(0);
+                    b.increment_by(8);
+                    assert_eq!(b.value(), 0);
+                    let mut c = c8!(0);
+                    c.increment_by(16);
+                    assert_eq!(c.value(), 0);
+                }
+
+                #[test]
+                fn cyclic_index_decrement_by_test() {
+                    let mut a = c8!(7);
+                    a.decrement_by(7);
+                    assert_eq!(a.value(), 0);
+                    let mut b = c8!(7);
+                    b.decrement_by(8);
+                    assert_eq!(b.value(), 7);
+                    let mut c = c8!(7);
+                    c.decrement_by(16);
+                    assert_eq!(c.value(), 7);
+                }
+
+                #[test]
+                fn cyclic_index_test_partial_eq_test() {
+                    let a = c8!(0);
+                    let b = c8!(0);
+                    assert_eq!(a, b);
+                    assert_eq!(a, a.clone());
+                }
+
+                #[test]
+                fn cyclic_index_test_operator_overloading_test() {
+                    let a = c8!(0);
+                    {
+                        let b = a + 1;
+                        assert_eq!(a, c8!(0));
+                        assert_eq!(b, c8!(1));
+                    }
+                    {
+                        let b = a - 1;
+                        assert_eq!(a, c8!(0));
+                        assert_eq!(b, c8!(7));
+                    }
+                    {
+                        let mut b = a;
+                        b += 1;
+                        assert_eq!(a, c8!(0));
+                        assert_eq!(b, c8!(1));
+                    }
+                    {
+                        let mut b = a;
+                        b -= 1;
+                        assert_eq!(a, c8!(0));
+                        assert_eq!(b, c8!(7));
+                    }
+                }
+
+                #[test]
+                fn cyclic_index_try_new_rejects_zero_size() {
+                    assert_eq!(CyclicIndex::<$ty>::try_new(0, 0), Err(CyclicIndexError::ZeroSize));
+                }
+
+                #[test]
+                fn cyclic_index_try_new_rejects_out_of_bounds_index() {
+                    assert_eq!(
+                        CyclicIndex::<$ty>::try_new(8, 8),
+                        Err(CyclicIndexError::IndexOutOfBounds { index: 8, size: 8 })
+                    );
+                }
+
+                #[test]
+                fn cyclic_index_try_new_accepts_valid_state() {
+                    assert_eq!(CyclicIndex::<$ty>::try_new(3, 8).unwrap(), c8!(3));
+                }
+
+                #[test]
+                fn cyclic_index_try_from_tuple() {
+                    use std::convert::TryFrom;
+                    assert_eq!(CyclicIndex::<$ty>::try_from((3, 8)).unwrap(), c8!(3));
+                    assert_eq!(
+                        CyclicIndex::<$ty>::try_from((8, 8)),
+                        Err(CyclicIndexError::IndexOutOfBounds { index: 8, size: 8 })
+                    );
+                }
+
+                #[test]
+                fn cyclic_index_checked_increment_by_reports_wrap() {
+                    let mut a = c8!(0);
+                    assert!(!a.checked_increment_by(7));
+                    assert_eq!(a.value(), 7);
+                    assert!(a.checked_increment_by(1));
+                    assert_eq!(a.value(), 0);
+                }
+
+                #[test]
+                fn cyclic_index_checked_decrement_by_reports_wrap() {
+                    let mut a = c8!(7);
+                    assert!(!a.checked_decrement_by(7));
+                    assert_eq!(a.value(), 0);
+                    assert!(a.checked_decrement_by(1));
+                    assert_eq!(a.value(), 7);
+                }
+
+                #[test]
+                fn cyclic_index_checked_increment_and_decrement_by_exact_multiple_of_size_both_wrap() {
+                    // A full lap in either direction lands back on the same index, and both
+                    // directions must agree that this counts as a wrap.
+                    let mut a = c8!(3);
+                    assert!(a.checked_increment_by(8));
+                    assert_eq!(a.value(), 3);
+                    let mut b = c8!(3);
+                    assert!(b.checked_decrement_by(8));
+                    assert_eq!(b.value(), 3);
+                }
+            }
+        };
     }
 
+    cyclic_index_tests!(usize_width, usize);
+    cyclic_index_tests!(u8_width, u8);
+    cyclic_index_tests!(u32_width, u32);
+
     #[test]
-    fn cyclic_index_decrement_by_test() {
-        let mut a = c8!(7);
-        a.decrement_by(7);
-        assert_eq!(a.value(), 0);
-        let mut b = c8!(7);
-        b.decrement_by(8);
-        assert_eq!(b.value(), 7);
-        let mut c = c8!(7);
-        c.increment_by(16);
-        assert_eq!(c.value(), 7);
+    fn cyclic_index_default_width_is_usize() {
+        let a: CyclicIndex = CyclicIndex::new(0, 8);
+        let b = CyclicIndex::<usize>::new(0, 8);
+        assert_eq!(a, b);
     }
 
     #[test]
-    fn cyclic_index_test_partial_eq_test() {
-        let a = c8!(0);
-        let b = c8!(0);
-        assert_eq!(a, b);
-        assert_eq!(a, a.clone());
+    fn cyclic_index_increment_by_does_not_overflow_native_width() {
+        // `size` (253) is well under `u8::MAX` (255), so the index must wrap at `size`, not at
+        // the native width of `u8`.
+        let mut a = CyclicIndex::<u8>::new(250, 253);
+        a.increment_by(10);
+        assert_eq!(a.value(), 7);
     }
 
     #[test]
-    fn cyclic_index_test_operator_overloading_test() {
-        let mut a = c8!(0);
-        {
-            let mut b = a + 1;
-            assert_eq!(a, c8!(0));
-            assert_eq!(b, c8!(1));
-        }
-        {
-            let mut b = a - 1;
-            assert_eq!(a, c8!(0));
-            assert_eq!(b, c8!(7));
-        }
-        {
-            let mut b = a;
-            b += 1;
-            assert_eq!(a, c8!(0));
-            assert_eq!(b, c8!(1));
-        }
-        {
-            let mut b = a;
-            b -= 1;
-            assert_eq!(a, c8!(0));
-            assert_eq!(b, c8!(7));
-        }
+    fn cyclic_index_decrement_by_does_not_overflow_native_width() {
+        let mut a = CyclicIndex::<u8>::new(2, 253);
+        a.decrement_by(10);
+        assert_eq!(a.value(), 245);
     }
 }
@@ -0,0 +1,2 @@
+pub mod cyclic_group;
+pub mod probability;
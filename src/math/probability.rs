@@ -1,4 +1,5 @@
-use std::fmt::{Debug, Display};
+use std::convert;
+use std::fmt::Debug;
 use std::ops::{Add, Div, Mul, Sub};
 
 #[derive(Copy, Clone, Debug, PartialOrd, PartialEq)]
@@ -35,11 +36,106 @@ impl<R: Copy + Debug + PartialOrd + PartialEq> Sub for Probability<R>
 
     fn sub(self, rhs: Self) -> Self::Output {
         Probability {
-            inner: (self.inner - rhs.inner) / (R::from(1.0) - rhs.inner.into())
+            inner: (self.inner - rhs.inner) / (R::from(1.0) - rhs.inner)
         }
     }
 }
 
+/// Floating point operations that are not exposed uniformly through `std::ops`, needed to move
+/// between `Probability` and its log-domain counterpart without round-tripping through lossy
+/// `ln`/`exp`.
+pub trait LogOps: Copy {
+    /// `ln(1 + self)`, accurate even when `self` is close to zero.
+    fn ln1p(self) -> Self;
+    /// `exp(self) - 1`, accurate even when `self` is close to zero.
+    fn expm1(self) -> Self;
+}
+
+impl LogOps for f32 {
+    fn ln1p(self) -> Self { self.ln_1p() }
+    fn expm1(self) -> Self { self.exp_m1() }
+}
+
+impl LogOps for f64 {
+    fn ln1p(self) -> Self { self.ln_1p() }
+    fn expm1(self) -> Self { self.exp_m1() }
+}
+
+/// A `Probability` stored as `w = ln(1 - p)` instead of `p` directly.
+///
+/// `a + b - a * b` and `(a - b) / (1 - b)` both lose precision to catastrophic cancellation as
+/// `a`/`b` approach `1.0`. In log space the same noisy-OR combinator becomes a plain sum:
+///
+/// 1 - (A (+) B) = (1 - A)(1 - B)
+/// ln(1 - (A (+) B)) = ln(1 - A) + ln(1 - B)
+/// w_result = w_a + w_b
+///
+/// and its inverse, R (-) A = B where 1 - B = (1 - R) / (1 - A), becomes:
+///
+/// w_b = w_r - w_a
+///
+/// Conversions to/from `Probability` go through `ln1p`/`expm1` rather than `ln`/`exp` directly
+/// so that `p` near `0.0` or `1.0` does not lose precision on the way in or out.
+#[derive(Copy, Clone, Debug, PartialOrd, PartialEq)]
+#[repr(transparent)]
+pub struct LogProbability<R: Copy + Debug + PartialOrd + PartialEq> {
+    inner: R
+}
+
+impl<R: Copy + Debug + PartialOrd + PartialEq> Add for LogProbability<R>
+    where R: Add<Output=R>,
+{
+    type Output = LogProbability<R>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        LogProbability {
+            inner: self.inner + rhs.inner
+        }
+    }
+}
+
+impl<R: Copy + Debug + PartialOrd + PartialEq> Sub for LogProbability<R>
+    where R: Sub<Output=R>,
+{
+    type Output = LogProbability<R>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        LogProbability {
+            inner: self.inner - rhs.inner
+        }
+    }
+}
+
+/// A `LogProbability` whose `w` is greater than `0.0`, so it does not correspond to any `p`
+/// in `[0.0, 1.0]`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct InvalidLogProbability;
+
+impl<R> convert::From<Probability<R>> for LogProbability<R>
+    where R: Copy + Debug + PartialOrd + PartialEq + LogOps + Sub<Output=R> + From<f64>,
+{
+    fn from(p: Probability<R>) -> Self {
+        LogProbability {
+            inner: (R::from(0.0) - p.inner).ln1p()
+        }
+    }
+}
+
+impl<R> convert::TryFrom<LogProbability<R>> for Probability<R>
+    where R: Copy + Debug + PartialOrd + PartialEq + LogOps + Sub<Output=R> + From<f64>,
+{
+    type Error = InvalidLogProbability;
+
+    fn try_from(w: LogProbability<R>) -> Result<Self, Self::Error> {
+        if w.inner > R::from(0.0) {
+            return Err(InvalidLogProbability);
+        }
+        Ok(Probability {
+            inner: R::from(0.0) - w.inner.expm1()
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -63,6 +159,7 @@ mod tests {
     fn probability_conversion() {
         let a: Probability<f32> = p!(0.5f32);
         let b: Probability<f64> = p!(a.inner.into());
+        assert_eq!(b, p!(0.5f64));
     }
 
     #[test]
@@ -89,5 +186,42 @@ mod tests {
         let b = p!(0.2);
         assert_eq!(a + b, b + a);
     }
+
+    macro_rules! w {
+        ($x:expr) => {
+            LogProbability {inner: $x}
+        };
+    }
+
+    #[test]
+    fn log_probability_zero_and_one() {
+        let zero: LogProbability<f64> = p!(0.0).into();
+        assert_eq!(zero, w!(0.0));
+        let one: LogProbability<f64> = p!(1.0).into();
+        assert_eq!(one, w!(f64::NEG_INFINITY));
+    }
+
+    #[test]
+    fn log_probability_round_trips_through_probability() {
+        let original = p!(0.999999);
+        let roundtripped: Probability<f64> =
+            std::convert::TryFrom::try_from(LogProbability::from(original)).unwrap();
+        assert!((original.inner - roundtripped.inner).abs() < 1e-12);
+    }
+
+    #[test]
+    fn log_probability_adding_matches_probability_adding() {
+        let a = p!(0.9999);
+        let b = p!(0.9998);
+        let combined: Probability<f64> =
+            std::convert::TryFrom::try_from(LogProbability::from(a) + LogProbability::from(b)).unwrap();
+        assert!((combined.inner - (a + b).inner).abs() < 1e-9);
+    }
+
+    #[test]
+    fn log_probability_rejects_positive_w() {
+        let invalid: LogProbability<f64> = w!(1.0);
+        assert!(std::convert::TryFrom::try_from(invalid).map(|_: Probability<f64>| ()).is_err());
+    }
 }
 
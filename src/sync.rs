@@ -0,0 +1,167 @@
+//! Lock-free ring buffer for a single producer and a single consumer.
+
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::math::cyclic_group::{Cyclic, CyclicIndex};
+
+/// Advances `index` by one slot, wrapping at `capacity`. Reuses `CyclicIndex`'s wraparound
+/// arithmetic so the modulo logic isn't duplicated here.
+fn advance(index: usize, capacity: usize) -> usize {
+    CyclicIndex::new(index, capacity).increment().value()
+}
+
+/// A fixed-capacity ring buffer that a single producer and a single consumer can `push`/`pop`
+/// concurrently without a mutex.
+///
+/// One extra slot is reserved internally so that `head == tail` unambiguously means "empty"
+/// rather than being ambiguous with "full".
+pub struct Cycle<T> {
+    buffer: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    capacity: usize,
+    /// next slot the consumer will read from
+    head: AtomicUsize,
+    /// next slot the producer will write into
+    tail: AtomicUsize,
+}
+
+unsafe impl<T: Send> Send for Cycle<T> {}
+unsafe impl<T: Send> Sync for Cycle<T> {}
+
+impl<T> Cycle<T> {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity + 1;
+        Cycle {
+            buffer: (0..capacity)
+                .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+                .collect::<Vec<_>>()
+                .into_boxed_slice(),
+            capacity,
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T> Cycle<T> {
+    /// Pushes `value`, handing it back on failure if the buffer is full.
+    ///
+    /// The `tail` is loaded with `Ordering::Relaxed` and the slot write happens before the CAS
+    /// that publishes the new `tail`, so calling `push` concurrently from more than one thread is
+    /// undefined behavior (two producers can write the same slot with no synchronization between
+    /// them) rather than a safe failure. The CAS only guards against a single producer's own
+    /// `tail` changing out from under it; it does not make this safe for multiple producers.
+    pub fn push(&self, value: T) -> Result<(), T> {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let next_tail = advance(tail, self.capacity);
+        if next_tail == self.head.load(Ordering::Acquire) {
+            return Err(value);
+        }
+        unsafe { (*self.buffer[tail].get()).write(value); }
+        match self.tail.compare_exchange(tail, next_tail, Ordering::Release, Ordering::Relaxed) {
+            Ok(_) => Ok(()),
+            Err(_) => unreachable!("single producer: tail cannot change concurrently"),
+        }
+    }
+
+    /// Pops the oldest value, or `None` if the buffer is empty.
+    pub fn pop(&self) -> Option<T> {
+        let head = self.head.load(Ordering::Relaxed);
+        if head == self.tail.load(Ordering::Acquire) {
+            return None;
+        }
+        let value = unsafe { (*self.buffer[head].get()).assume_init_read() };
+        let next_head = advance(head, self.capacity);
+        match self.head.compare_exchange(head, next_head, Ordering::Release, Ordering::Relaxed) {
+            Ok(_) => Some(value),
+            Err(_) => unreachable!("single consumer: head cannot change concurrently"),
+        }
+    }
+}
+
+#[cfg(not(target_has_atomic = "ptr"))]
+impl<T> Cycle<T> {
+    /// Degraded mode for targets without a pointer-width CAS: plain loads/stores only.
+    pub fn push(&self, value: T) -> Result<(), T> {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let next_tail = advance(tail, self.capacity);
+        if next_tail == self.head.load(Ordering::Acquire) {
+            return Err(value);
+        }
+        unsafe { (*self.buffer[tail].get()).write(value); }
+        self.tail.store(next_tail, Ordering::Release);
+        Ok(())
+    }
+
+    /// Pops the oldest value, or `None` if the buffer is empty.
+    pub fn pop(&self) -> Option<T> {
+        let head = self.head.load(Ordering::Relaxed);
+        if head == self.tail.load(Ordering::Acquire) {
+            return None;
+        }
+        let value = unsafe { (*self.buffer[head].get()).assume_init_read() };
+        let next_head = advance(head, self.capacity);
+        self.head.store(next_head, Ordering::Release);
+        Some(value)
+    }
+}
+
+impl<T> Drop for Cycle<T> {
+    fn drop(&mut self) {
+        let mut head = *self.head.get_mut();
+        let tail = *self.tail.get_mut();
+        while head != tail {
+            unsafe { (*self.buffer[head].get()).assume_init_drop(); }
+            head = advance(head, self.capacity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn empty_buffer_pop_returns_none() {
+        let cycle: Cycle<i32> = Cycle::new(4);
+        assert_eq!(cycle.pop(), None);
+    }
+
+    #[test]
+    fn full_buffer_rejects_push() {
+        let cycle = Cycle::new(2);
+        assert!(cycle.push(1).is_ok());
+        assert!(cycle.push(2).is_ok());
+        assert_eq!(cycle.push(3), Err(3));
+        assert_eq!(cycle.pop(), Some(1));
+        assert!(cycle.push(3).is_ok());
+    }
+
+    #[test]
+    fn cross_thread_producer_consumer_preserves_order() {
+        let cycle = Arc::new(Cycle::new(16));
+        let producer_cycle = cycle.clone();
+        let producer = thread::spawn(move || {
+            for i in 0..10_000 {
+                while producer_cycle.push(i).is_err() {
+                    thread::yield_now();
+                }
+            }
+        });
+
+        let mut received = Vec::with_capacity(10_000);
+        while received.len() < 10_000 {
+            match cycle.pop() {
+                Some(value) => received.push(value),
+                None => thread::yield_now(),
+            }
+        }
+        producer.join().unwrap();
+
+        assert_eq!(received, (0..10_000).collect::<Vec<_>>());
+    }
+}